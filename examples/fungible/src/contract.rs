@@ -0,0 +1,646 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod proof;
+mod state;
+
+use async_trait::async_trait;
+use fungible::{
+    Account, AccountOwner, ApplicationCall, Commitment, ConfidentialTransferProof, Destination,
+    FungibleTokenAbi, InitialState, Message, Operation, RangeProof, SessionCall, TokenMetadata,
+};
+use linera_sdk::{
+    base::{Amount, SessionId, WithContractAbi},
+    contract::system_api,
+    ApplicationCallOutcome, CalleeContext, Contract, ExecutionOutcome, MessageContext,
+    OperationContext, SessionCallOutcome, ViewStateStorage,
+};
+use state::FungibleTokenState;
+use thiserror::Error;
+
+linera_sdk::contract!(FungibleTokenContract);
+
+impl WithContractAbi for FungibleTokenContract {
+    type Abi = FungibleTokenAbi;
+}
+
+pub struct FungibleTokenContract {
+    state: FungibleTokenState,
+}
+
+#[async_trait]
+impl Contract for FungibleTokenContract {
+    type Error = Error;
+    type Storage = ViewStateStorage<Self>;
+
+    async fn initialize(
+        &mut self,
+        _context: &OperationContext,
+        state: InitialState,
+    ) -> Result<ExecutionOutcome<Message>, Self::Error> {
+        for (owner, amount) in state.accounts {
+            self.state.accounts.insert(&owner, amount)?;
+            self.adjust_total_supply(amount, true).await?;
+        }
+        Ok(ExecutionOutcome::default())
+    }
+
+    async fn execute_operation(
+        &mut self,
+        context: &OperationContext,
+        operation: Operation,
+    ) -> Result<ExecutionOutcome<Message>, Self::Error> {
+        match operation {
+            Operation::Transfer {
+                owner,
+                amount,
+                target_account,
+            } => {
+                Self::check_account_authentication(context.authenticated_signer, owner)?;
+                self.debit_and_forward(owner, amount, target_account).await
+            }
+
+            Operation::Claim {
+                source_account,
+                amount,
+                target_account,
+            } => {
+                Self::check_account_authentication(
+                    context.authenticated_signer,
+                    source_account.owner,
+                )?;
+                Ok(self.claim(source_account, amount, target_account))
+            }
+
+            Operation::WithdrawFees {
+                amount,
+                target_account,
+            } => {
+                let policy = Self::token_metadata()
+                    .fee_policy
+                    .ok_or(Error::NoFeePolicy)?;
+                Self::check_account_authentication(
+                    context.authenticated_signer,
+                    policy.fee_collector.owner,
+                )?;
+                let accumulated = *self.state.accumulated_fees.get();
+                let remaining = accumulated
+                    .try_sub(amount)
+                    .map_err(|_| Error::InsufficientBalance)?;
+                self.state.accumulated_fees.set(remaining);
+                Ok(self.finish_transfer_to_account(amount, target_account))
+            }
+            Operation::Mint { to, amount } => {
+                let authority = Self::token_metadata()
+                    .mint_authority
+                    .ok_or(Error::MintingDisabled)?;
+                Self::check_mint_authority(context.authenticated_signer, authority)?;
+
+                if to.chain_id == system_api::current_chain_id() {
+                    self.credit(to.owner, amount).await?;
+                    self.adjust_total_supply(amount, true).await?;
+                    Ok(ExecutionOutcome::default())
+                } else {
+                    Ok(ExecutionOutcome::default().with_message(
+                        to.chain_id,
+                        Message::Mint {
+                            owner: to.owner,
+                            amount,
+                        },
+                    ))
+                }
+            }
+
+            Operation::Burn { from, amount } => {
+                let signer = context
+                    .authenticated_signer
+                    .ok_or(Error::IncorrectAuthentication)?;
+                let spender = AccountOwner::from(signer);
+                if spender != from {
+                    self.spend_allowance(from, spender, amount).await?;
+                }
+                self.debit(from, amount).await?;
+                self.adjust_total_supply(amount, false).await?;
+                Ok(ExecutionOutcome::default())
+            }
+
+            Operation::Approve { spender, amount } => {
+                let owner = AccountOwner::from(
+                    context
+                        .authenticated_signer
+                        .ok_or(Error::IncorrectAuthentication)?,
+                );
+                self.state.allowances.insert(&(owner, spender), amount)?;
+                Ok(ExecutionOutcome::default())
+            }
+
+            Operation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                Self::check_account_authentication(context.authenticated_signer, spender)?;
+                self.spend_allowance(owner, spender, amount).await?;
+                self.debit_and_forward(owner, amount, target_account).await
+            }
+            Operation::ConfidentialTransfer {
+                owner,
+                target_account,
+                proof,
+            } => {
+                Self::check_account_authentication(context.authenticated_signer, owner)?;
+                let policy = Self::token_metadata()
+                    .confidential_policy
+                    .ok_or(Error::ConfidentialTransfersDisabled)?;
+                if policy.auditor.is_some() && proof.auditor_ciphertext.is_none() {
+                    return Err(Error::MissingAuditorCiphertext);
+                }
+
+                let old_balance = self
+                    .state
+                    .confidential_balances
+                    .get(&owner)
+                    .await?
+                    .unwrap_or_else(proof::zero_commitment);
+                proof::verify_confidential_transfer(old_balance, &proof)?;
+
+                self.state
+                    .confidential_balances
+                    .insert(&owner, proof.sender_new_balance)?;
+
+                if target_account.chain_id == system_api::current_chain_id() {
+                    self.credit_confidential(target_account.owner, proof.receiver_delta)
+                        .await?;
+                    Ok(ExecutionOutcome::default())
+                } else {
+                    Ok(ExecutionOutcome::default().with_message(
+                        target_account.chain_id,
+                        Message::ConfidentialCredit {
+                            owner: target_account.owner,
+                            receiver_delta: proof.receiver_delta,
+                        },
+                    ))
+                }
+            }
+        }
+    }
+
+    async fn execute_message(
+        &mut self,
+        _context: &MessageContext,
+        message: Message,
+    ) -> Result<ExecutionOutcome<Message>, Self::Error> {
+        match message {
+            Message::Credit { owner, amount } => {
+                self.credit(owner, amount).await?;
+                Ok(ExecutionOutcome::default())
+            }
+
+            Message::Withdraw {
+                owner,
+                amount,
+                target_account,
+            } => self.debit_and_forward(owner, amount, target_account).await,
+
+            Message::Mint { owner, amount } => {
+                self.credit(owner, amount).await?;
+                self.adjust_total_supply(amount, true).await?;
+                Ok(ExecutionOutcome::default())
+            }
+
+            Message::CreditFee { amount } => {
+                let accumulated = *self.state.accumulated_fees.get();
+                self.state
+                    .accumulated_fees
+                    .set(accumulated.saturating_add(amount));
+                Ok(ExecutionOutcome::default())
+            }
+            Message::ConfidentialCredit {
+                owner,
+                receiver_delta,
+            } => {
+                self.credit_confidential(owner, receiver_delta).await?;
+                Ok(ExecutionOutcome::default())
+            }
+        }
+    }
+
+    async fn handle_application_call(
+        &mut self,
+        context: &CalleeContext,
+        call: ApplicationCall,
+        _forwarded_sessions: Vec<SessionId>,
+    ) -> Result<ApplicationCallOutcome<Message, Amount>, Self::Error> {
+        match call {
+            ApplicationCall::Balance { owner } => {
+                let balance = self.balance(owner).await?;
+                Ok(ApplicationCallOutcome {
+                    value: balance,
+                    ..ApplicationCallOutcome::default()
+                })
+            }
+
+            ApplicationCall::Transfer {
+                owner,
+                amount,
+                destination,
+            } => {
+                Self::check_account_authentication(context.authenticated_signer, owner)?;
+
+                let execution_outcome = match destination {
+                    Destination::Account(account) => {
+                        self.debit(owner, amount).await?;
+                        self.finish_transfer_to_account(amount, account)
+                    }
+                    Destination::NewSession => self.reject_transfer_to_session(amount)?,
+                };
+
+                Ok(ApplicationCallOutcome {
+                    execution_outcome,
+                    value: Amount::ZERO,
+                    ..ApplicationCallOutcome::default()
+                })
+            }
+
+            ApplicationCall::Claim {
+                source_account,
+                amount,
+                target_account,
+            } => {
+                Self::check_account_authentication(
+                    context.authenticated_signer,
+                    source_account.owner,
+                )?;
+
+                Ok(ApplicationCallOutcome {
+                    execution_outcome: self.claim(source_account, amount, target_account),
+                    value: Amount::ZERO,
+                    ..ApplicationCallOutcome::default()
+                })
+            }
+
+            ApplicationCall::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                Self::check_account_authentication(context.authenticated_signer, spender)?;
+                self.spend_allowance(owner, spender, amount).await?;
+                Ok(ApplicationCallOutcome {
+                    execution_outcome: self.debit_and_forward(owner, amount, target_account).await?,
+                    value: Amount::ZERO,
+                    ..ApplicationCallOutcome::default()
+                })
+            }
+        }
+    }
+
+    async fn handle_session_call(
+        &mut self,
+        _context: &CalleeContext,
+        session_balance: Amount,
+        call: SessionCall,
+        _forwarded_sessions: Vec<SessionId>,
+    ) -> Result<SessionCallOutcome<Message, Amount, Amount>, Self::Error> {
+        match call {
+            SessionCall::Balance => Ok(SessionCallOutcome {
+                application_call_outcome: ApplicationCallOutcome {
+                    value: session_balance,
+                    ..ApplicationCallOutcome::default()
+                },
+                new_session_state: Some(session_balance),
+            }),
+
+            SessionCall::Transfer {
+                amount,
+                destination,
+            } => {
+                let remaining = session_balance
+                    .try_sub(amount)
+                    .map_err(|_| Error::InsufficientBalance)?;
+
+                let execution_outcome = match destination {
+                    Destination::Account(account) => self.finish_transfer_to_account(amount, account),
+                    Destination::NewSession => self.reject_transfer_to_session(amount)?,
+                };
+
+                Ok(SessionCallOutcome {
+                    application_call_outcome: ApplicationCallOutcome {
+                        execution_outcome,
+                        value: Amount::ZERO,
+                        ..ApplicationCallOutcome::default()
+                    },
+                    new_session_state: (remaining > Amount::ZERO).then_some(remaining),
+                })
+            }
+        }
+    }
+}
+
+impl FungibleTokenContract {
+    /// Returns the parameters (the on-chain `TokenMetadata`) this instance was created with.
+    fn token_metadata() -> TokenMetadata {
+        Self::parameters().expect("Application parameters must be set")
+    }
+
+    /// Verifies that `authenticated_signer` is allowed to act on behalf of `owner`, i.e. that a
+    /// user can only move tokens out of their own account.
+    fn check_account_authentication(
+        authenticated_signer: Option<linera_sdk::base::Owner>,
+        owner: AccountOwner,
+    ) -> Result<(), Error> {
+        match owner {
+            AccountOwner::User(owner) if authenticated_signer == Some(owner) => Ok(()),
+            AccountOwner::Application(_) => Ok(()),
+            _ => Err(Error::IncorrectAuthentication),
+        }
+    }
+
+    /// Verifies that `authenticated_signer` is the `mint_authority`. Unlike
+    /// `check_account_authentication`, an `AccountOwner::Application` authority is never
+    /// accepted here: an `Operation` only ever authenticates a user signer, so an application
+    /// authority can never legitimately be matched this way and must always be rejected.
+    fn check_mint_authority(
+        authenticated_signer: Option<linera_sdk::base::Owner>,
+        authority: AccountOwner,
+    ) -> Result<(), Error> {
+        match authority {
+            AccountOwner::User(owner) if authenticated_signer == Some(owner) => Ok(()),
+            _ => Err(Error::IncorrectAuthentication),
+        }
+    }
+
+    /// Returns `owner`'s current plaintext balance.
+    async fn balance(&mut self, owner: AccountOwner) -> Result<Amount, Error> {
+        Ok(self
+            .state
+            .accounts
+            .get(&owner)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Removes `amount` from `owner`'s account, failing if the balance is insufficient.
+    async fn debit(&mut self, owner: AccountOwner, amount: Amount) -> Result<(), Error> {
+        let balance = self.balance(owner).await?;
+        let new_balance = balance.try_sub(amount).map_err(|_| Error::InsufficientBalance)?;
+        if new_balance == Amount::ZERO {
+            self.state.accounts.remove(&owner)?;
+        } else {
+            self.state.accounts.insert(&owner, new_balance)?;
+        }
+        Ok(())
+    }
+
+    /// Adds `amount` to `owner`'s account.
+    async fn credit(&mut self, owner: AccountOwner, amount: Amount) -> Result<(), Error> {
+        let balance = self.balance(owner).await?;
+        self.state
+            .accounts
+            .insert(&owner, balance.saturating_add(amount))?;
+        Ok(())
+    }
+
+    /// Debits `amount` from `owner`, deducts any configured transfer fee, and forwards the net
+    /// amount on to `target_account`.
+    async fn debit_and_forward(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    ) -> Result<ExecutionOutcome<Message>, Error> {
+        self.enforce_withdrawal_limit(owner, amount).await?;
+        self.debit(owner, amount).await?;
+        let (net_amount, outcome) = self.charge_fee(amount).await?;
+        Ok(outcome.with_message(
+            target_account.chain_id,
+            Message::Credit {
+                owner: target_account.owner,
+                amount: net_amount,
+            },
+        ))
+    }
+
+    /// Deducts the fee owed on `amount` under the instance's `fee_policy`, if any, crediting it
+    /// to the `fee_collector` either directly (if local) or via a `CreditFee` message. Returns
+    /// the amount that remains after the fee.
+    async fn charge_fee(
+        &mut self,
+        amount: Amount,
+    ) -> Result<(Amount, ExecutionOutcome<Message>), Error> {
+        let Some(policy) = Self::token_metadata().fee_policy else {
+            return Ok((amount, ExecutionOutcome::default()));
+        };
+        let fee = policy.compute_fee(amount);
+        let net_amount = amount.try_sub(fee).unwrap_or(Amount::ZERO);
+        if fee == Amount::ZERO {
+            return Ok((net_amount, ExecutionOutcome::default()));
+        }
+
+        if policy.fee_collector.chain_id == system_api::current_chain_id() {
+            let accumulated = *self.state.accumulated_fees.get();
+            self.state.accumulated_fees.set(accumulated.saturating_add(fee));
+            Ok((net_amount, ExecutionOutcome::default()))
+        } else {
+            let outcome = ExecutionOutcome::default()
+                .with_message(policy.fee_collector.chain_id, Message::CreditFee { amount: fee });
+            Ok((net_amount, outcome))
+        }
+    }
+
+    /// Credits `amount` to `account` by sending it a `Credit` message.
+    fn finish_transfer_to_account(
+        &self,
+        amount: Amount,
+        account: Account,
+    ) -> ExecutionOutcome<Message> {
+        ExecutionOutcome::default().with_message(
+            account.chain_id,
+            Message::Credit {
+                owner: account.owner,
+                amount,
+            },
+        )
+    }
+
+    /// Would open a new session holding `amount` tokens, but session creation is not implemented
+    /// yet. Callers must reject `Destination::NewSession` via this before debiting `amount` from
+    /// any account, so that a rejected transfer never destroys the funds it was meant to move.
+    fn reject_transfer_to_session(&self, _amount: Amount) -> Result<ExecutionOutcome<Message>, Error> {
+        Err(Error::NotYetSupported)
+    }
+
+    /// Homomorphically adds `delta` to `owner`'s confidential balance commitment.
+    async fn credit_confidential(
+        &mut self,
+        owner: AccountOwner,
+        delta: Commitment,
+    ) -> Result<(), Error> {
+        let current = self
+            .state
+            .confidential_balances
+            .get(&owner)
+            .await?
+            .unwrap_or_else(proof::zero_commitment);
+        self.state
+            .confidential_balances
+            .insert(&owner, proof::add(current, delta)?)?;
+        Ok(())
+    }
+
+    /// Checks `owner`'s cumulative withdrawals against the instance's `withdrawal_limit_policy`,
+    /// if any, resetting the rolling window once it has elapsed, and records `amount` against
+    /// it. `amount` and `withdrawal_limit_per_epoch` are both plain `Amount`s in the same
+    /// atto-scale representation as every other quantity in this application, so they compare
+    /// directly; `TokenMetadata::decimals` is purely a client-side display/parsing hint and
+    /// does not need to be read here.
+    async fn enforce_withdrawal_limit(
+        &mut self,
+        owner: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), Error> {
+        let Some(policy) = Self::token_metadata().withdrawal_limit_policy else {
+            return Ok(());
+        };
+
+        let now = system_api::current_system_time();
+        let (window_start, spent) = self
+            .state
+            .withdrawal_usage
+            .get(&owner)
+            .await?
+            .filter(|(window_start, _)| {
+                now.micros().saturating_sub(window_start.micros()) < policy.epoch_duration_micros
+            })
+            .unwrap_or((now, Amount::ZERO));
+
+        let new_spent = spent.saturating_add(amount);
+        if new_spent > policy.withdrawal_limit_per_epoch {
+            return Err(Error::WithdrawalLimitExceeded);
+        }
+
+        self.state
+            .withdrawal_usage
+            .insert(&owner, (window_start, new_spent))?;
+        Ok(())
+    }
+
+    /// Decrements the allowance `owner` has granted `spender` by `amount`, failing if it is
+    /// insufficient.
+    async fn spend_allowance(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+    ) -> Result<(), Error> {
+        let key = (owner, spender);
+        let allowance = self.state.allowances.get(&key).await?.unwrap_or_default();
+        let remaining = allowance
+            .try_sub(amount)
+            .map_err(|_| Error::InsufficientAllowance)?;
+        if remaining == Amount::ZERO {
+            self.state.allowances.remove(&key)?;
+        } else {
+            self.state.allowances.insert(&key, remaining)?;
+        }
+        Ok(())
+    }
+
+    /// Increases (`increase = true`) or decreases the recorded total supply by `amount`. Like
+    /// `accounts`, this is tracked per chain: it reflects the initial state and the `Mint`/`Burn`
+    /// operations and messages processed on this chain, not a single global counter. Burning
+    /// more on a chain than was minted there (e.g. tokens claimed from another chain) leaves
+    /// the recorded value at zero rather than rejecting the burn.
+    async fn adjust_total_supply(&mut self, amount: Amount, increase: bool) -> Result<(), Error> {
+        let total_supply = *self.state.total_supply.get();
+        let total_supply = if increase {
+            total_supply.saturating_add(amount)
+        } else {
+            total_supply.saturating_sub(amount)
+        };
+        self.state.total_supply.set(total_supply);
+        Ok(())
+    }
+
+    /// Sends a `Withdraw` message to `source_account`'s chain, which will debit it and forward
+    /// the tokens on to `target_account`.
+    fn claim(
+        &self,
+        source_account: Account,
+        amount: Amount,
+        target_account: Account,
+    ) -> ExecutionOutcome<Message> {
+        ExecutionOutcome::default().with_message(
+            source_account.chain_id,
+            Message::Withdraw {
+                owner: source_account.owner,
+                amount,
+                target_account,
+            },
+        )
+    }
+}
+
+/// An error that can occur while executing the fungible token application.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Failed to deserialize the BCS-encoded operation, message or parameters.
+    #[error("Failed to deserialize BCS bytes")]
+    BcsError(#[from] bcs::Error),
+
+    /// Failed to deserialize the JSON-encoded initial state.
+    #[error("Failed to deserialize JSON string")]
+    JsonError(#[from] serde_json::Error),
+
+    /// A view-storage error.
+    #[error(transparent)]
+    ViewError(#[from] linera_sdk::views::ViewError),
+
+    /// The requested transfer does not have enough funds behind it.
+    #[error("Insufficient balance for transfer")]
+    InsufficientBalance,
+
+    /// An operation or message was rejected because the authenticated signer does not control
+    /// the account it is trying to move funds out of.
+    #[error("The requested transfer is not correctly authenticated")]
+    IncorrectAuthentication,
+
+    /// `WithdrawFees` was executed on an instance that was not configured with a `fee_policy`.
+    #[error("This instance does not have a fee policy, so there are no fees to withdraw")]
+    NoFeePolicy,
+
+    /// `Mint` was executed on an instance that was not configured with a `mint_authority`.
+    #[error("This instance does not have a mint authority, so minting is disabled")]
+    MintingDisabled,
+
+    /// `TransferFrom` or an allowance-based `Burn` was attempted without a sufficient allowance.
+    #[error("Insufficient allowance for this transfer")]
+    InsufficientAllowance,
+
+    /// The transfer was rejected because it would exceed the owner's `withdrawal_limit_policy`
+    /// for the current epoch.
+    #[error("This transfer would exceed the withdrawal limit for the current epoch")]
+    WithdrawalLimitExceeded,
+
+    /// `ConfidentialTransfer` was executed on an instance that was not configured with a
+    /// `confidential_policy`.
+    #[error("This instance does not support confidential transfers")]
+    ConfidentialTransfersDisabled,
+
+    /// A `confidential_policy` with an `auditor` requires every proof to carry an
+    /// `auditor_ciphertext`, and this one did not.
+    #[error("Missing auditor_ciphertext required by this instance's confidential_policy")]
+    MissingAuditorCiphertext,
+
+    /// A `ConfidentialTransferProof` failed to verify.
+    #[error(transparent)]
+    ProofError(#[from] proof::ProofError),
+
+    /// Placeholder for operations whose behavior has not been implemented yet.
+    #[error("This operation is not yet supported")]
+    NotYetSupported,
+}
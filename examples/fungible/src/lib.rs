@@ -28,6 +28,10 @@ Tokens can be transferred from an account to different destinations, such as:
 - other accounts on other chains,
 - sessions so that other applications can use some tokens.
 
+Alternatively, an owner can `Approve` a spending limit for another account owner (typically
+another application) without creating a session, and that spender can then move up to the
+approved amount with `TransferFrom`, in the style of an ERC-20 allowance.
+
 # Usage
 
 ## Setting Up
@@ -77,9 +81,17 @@ Here, we stored the new bytecode ID in a variable `BYTECODE_ID` to be reused it
 ## Creating a Token
 
 In order to use the published bytecode to create a token application, the initial state must be
-specified. This initial state is where the tokens are minted. After the token is created, no
-additional tokens can be minted and added to the application. The initial state is a JSON string
-that specifies the accounts that start with tokens.
+specified. This initial state is where the tokens are minted. After the token is created,
+additional tokens can only be minted if the application was configured with a `mint_authority` in
+its `Parameters`, and then only by the owner matching that authority via the `Mint` operation. The
+initial state is a JSON string that specifies the accounts that start with tokens.
+
+Besides the initial accounts, bytecode publishers also choose the `Parameters` used to create the
+application instance. These parameters carry the on-chain `TokenMetadata` (`name`, `ticker` and
+`decimals`) that identifies this particular token, since the same bytecode can back many distinct
+token instances. The `decimals` field is the scale implied by string-encoded amounts such as
+`"100."` below, so wallets can render human-readable balances without hardcoding any assumption
+about it.
 
 In order to select the accounts to have initial tokens, the command below can be used to list
 the chains created for the test in the default wallet:
@@ -109,6 +121,7 @@ APP_ID=$(linera create-application $BYTECODE_ID \
     --json-argument "{ \"accounts\": {
         \"User:$OWNER_1\": \"100.\"
     } }" \
+    --json-parameters "{ \"name\": \"Example Token\", \"ticker\": \"EXT\", \"decimals\": 2 }" \
 )
 ```
 
@@ -127,6 +140,22 @@ PORT=8080
 linera service --port $PORT &
 ```
 
+The token's metadata can be queried at any time with the `tokenMetadata` GraphQL query, e.g.
+`query { tokenMetadata { name ticker decimals } }`, which simply echoes the `Parameters` the
+application was created with. If a `fee_policy` was configured, the fees accumulated so far
+can be queried with `query { withdrawableFees }`, and only the `fee_collector` account can
+withdraw them with the `WithdrawFees` operation. The supply recorded on the queried chain, which
+changes as `Mint` and `Burn` operations are processed there, is available with
+`query { totalSupply }`; it is tracked per chain, not as a single global counter. An
+owner's remaining allowance for a given spender, granted via the `Approve` operation and spent
+down by `TransferFrom`, can be queried with `query { allowance(owner: ..., spender: ...) }`.
+
+If the application was created with a `confidential_policy`, it also holds balances as
+Pedersen commitments, separately from the plaintext amounts, and moves them with
+`ConfidentialTransfer`; see [`ConfidentialTransferProof`] for what a client must supply.
+Plaintext balances and `Transfer` remain available in such an instance, and no operation
+converts a plaintext balance into a confidential one.
+
 Then the web frontend:
 
 ```bash
@@ -156,7 +185,7 @@ transfer tokens from OWNER_1 to OWNER_2 at CHAIN_2 will instantly update the UI
 second page.
 */
 
-use async_graphql::{scalar, InputObject, Request, Response};
+use async_graphql::{scalar, InputObject, Request, Response, SimpleObject};
 use linera_sdk::{
     base::{Amount, ApplicationId, ChainId, ContractAbi, Owner, ServiceAbi},
     graphql::GraphQLMutationRoot,
@@ -177,7 +206,7 @@ pub struct FungibleTokenAbi;
 
 impl ContractAbi for FungibleTokenAbi {
     type InitializationArgument = InitialState;
-    type Parameters = ();
+    type Parameters = TokenMetadata;
     type ApplicationCall = ApplicationCall;
     type Operation = Operation;
     type Message = Message;
@@ -189,13 +218,135 @@ impl ContractAbi for FungibleTokenAbi {
 impl ServiceAbi for FungibleTokenAbi {
     type Query = Request;
     type QueryResponse = Response;
-    type Parameters = ();
+    type Parameters = TokenMetadata;
+}
+
+/// The on-chain metadata that identifies a token instance created from this bytecode.
+///
+/// Every application instance created from the `fungible` bytecode represents a distinct
+/// token, so these parameters are what lets wallets and block explorers tell them apart and
+/// render balances consistently.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct TokenMetadata {
+    /// The human-readable name of the token, e.g. `"Example Token"`.
+    pub name: String,
+    /// The short ticker symbol of the token, e.g. `"EXT"`.
+    pub ticker: String,
+    /// The number of decimal places used when displaying or parsing amounts, matching the
+    /// scale implied by string-encoded `Amount`s such as `"100."`.
+    pub decimals: u8,
+    /// An optional transfer-fee policy, modeled on the token-2022 transfer-fee extension.
+    /// When set, every `Transfer`/`Claim` is charged a fee that is credited to the
+    /// configured treasury account instead of the recipient.
+    pub fee_policy: Option<FeePolicy>,
+    /// An optional elastic-supply authority. When set, the matching owner may submit `Mint`
+    /// operations to create new tokens after the application has been created. When unset,
+    /// the initial state is the only source of tokens, as with a fixed-supply token.
+    pub mint_authority: Option<AccountOwner>,
+    /// An optional per-account withdrawal rate limit, useful for faucet-style tokens and
+    /// basic abuse mitigation.
+    pub withdrawal_limit_policy: Option<WithdrawalLimitPolicy>,
+    /// Opt-in confidential-transfer mode, inspired by the token-2022 confidential
+    /// extension. When set, the `ConfidentialTransfer` operation moves balances held as
+    /// Pedersen commitments, which are kept separately from the plaintext `Amount` balances.
+    /// The plaintext balances and operations such as `Transfer` remain available, and neither
+    /// the initial state nor `Mint` credits a confidential balance.
+    pub confidential_policy: Option<ConfidentialPolicy>,
+}
+
+/// Configuration for an instance's confidential-transfer mode.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct ConfidentialPolicy {
+    /// An optional auditor account that can be handed decryptable amounts for every
+    /// confidential transfer, as an escape hatch for compliance. The auditor never learns
+    /// the blinding factors used in the on-chain commitments.
+    pub auditor: Option<AccountOwner>,
+}
+
+/// A Pedersen commitment `C = amount*G + blinding*H` over the Ristretto255 group, binding a
+/// balance or a transfer amount without revealing it. Stored as a compressed Ristretto point.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Commitment(pub [u8; 32]);
+
+scalar!(Commitment);
+
+/// A Bulletproofs range proof attesting that a committed value lies in `[0, 2^64)`, without
+/// revealing the value.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RangeProof(pub Vec<u8>);
+
+scalar!(RangeProof);
+
+/// Everything needed to verify and apply a confidential transfer without learning the
+/// transferred amount or either party's resulting balance.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConfidentialTransferProof {
+    /// The sender's updated balance commitment, `C_new = C_old - C_delta`.
+    pub sender_new_balance: Commitment,
+    /// The commitment to the transferred amount, added to the receiver's balance
+    /// commitment.
+    pub receiver_delta: Commitment,
+    /// Proves that both `sender_new_balance` and `receiver_delta` commit to values in
+    /// `[0, 2^64)`.
+    pub range_proof: RangeProof,
+    /// Proves that `sender_new_balance` and `receiver_delta` are consistent with the
+    /// sender's prior commitment, i.e. that the net change conserves value.
+    pub equality_proof: Vec<u8>,
+    /// The transfer amount, encrypted to the configured auditor's public key so that a
+    /// designated auditor can decrypt it without learning the blinding factors above. Only
+    /// present when the application's `ConfidentialPolicy` specifies an `auditor`.
+    pub auditor_ciphertext: Option<Vec<u8>>,
+}
+
+scalar!(ConfidentialTransferProof);
+
+/// A limit on how much a single account owner may transfer or claim out within one rolling
+/// window, reset once the window elapses.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct WithdrawalLimitPolicy {
+    /// The maximum amount a single account owner may withdraw within one epoch, denominated
+    /// like every other `Amount` in this application (e.g. `Transfer::amount`): `decimals` is
+    /// only a display/parsing hint for clients rendering the string form such as `"10."`, and
+    /// does not change how this value compares against other `Amount`s on-chain.
+    pub withdrawal_limit_per_epoch: Amount,
+    /// The length of the rolling window, in microseconds, after which an account owner's
+    /// accumulated spending resets.
+    pub epoch_duration_micros: u64,
+}
+
+/// A fee charged on every transfer of this token, paid to a fixed treasury account.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct FeePolicy {
+    /// The fee rate in basis points (1/100 of a percent) of the transferred amount.
+    pub fee_basis_points: u16,
+    /// The maximum fee that can be charged on a single transfer, regardless of its size.
+    pub max_fee: Amount,
+    /// The account that collects fees charged by this policy.
+    pub fee_collector: Account,
+}
+
+impl FeePolicy {
+    /// Computes the fee owed on a transfer of `amount`, as
+    /// `min(amount * fee_basis_points / 10_000, max_fee)`.
+    pub fn compute_fee(&self, amount: Amount) -> Amount {
+        // `u128::from(amount)` and `Amount::from_attos` both operate on the raw atto-scale
+        // integer. Going through `Amount::from(u128)` instead would be wrong: that
+        // constructor treats its argument as a whole-token count, re-scaling it by 10^18 and
+        // silently inflating the fee.
+        let fee_attos =
+            u128::from(amount).saturating_mul(u128::from(self.fee_basis_points)) / 10_000;
+        std::cmp::min(Amount::from_attos(fee_attos), self.max_fee)
+    }
 }
 
 /// An operation.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
-    /// A transfer from a (locally owned) account to a (possibly remote) account.
+    /// A transfer from a (locally owned) account to a (possibly remote) account. If the
+    /// application was created with a `fee_policy`, the fee is deducted from `amount` and
+    /// credited to the `fee_collector` instead of reaching `target_account`. If a
+    /// `withdrawal_limit_policy` is configured, this is rejected once `owner`'s cumulative
+    /// transfers within the current epoch would exceed `withdrawal_limit_per_epoch`.
     Transfer {
         owner: AccountOwner,
         amount: Amount,
@@ -203,18 +354,52 @@ pub enum Operation {
     },
     /// Same as transfer but the source account may be remote. Depending on its
     /// configuration (see also #464), the target chain may take time or refuse to process
-    /// the message.
+    /// the message. The configured transfer fee and withdrawal rate limit, if any, still
+    /// apply.
     Claim {
         source_account: Account,
         amount: Amount,
         target_account: Account,
     },
+    /// Withdraws accumulated fees to `target_account`. Only the `fee_collector` configured
+    /// in the application's `fee_policy` may execute this operation.
+    WithdrawFees { amount: Amount, target_account: Account },
+    /// Mints new tokens into `to`. Only the `mint_authority` configured in the
+    /// application's `Parameters` may execute this operation; rejected otherwise.
+    Mint { to: Account, amount: Amount },
+    /// Burns `amount` tokens from `from`'s account, reducing the total supply. The caller must
+    /// either be `from`, or hold an allowance from `from` of at least `amount`, in which case
+    /// the allowance is decremented as in `TransferFrom`.
+    Burn { from: AccountOwner, amount: Amount },
+    /// Approves `spender` to move up to `amount` of the caller's tokens via `TransferFrom`,
+    /// replacing any previously approved amount for that `(owner, spender)` pair.
+    Approve { spender: AccountOwner, amount: Amount },
+    /// Transfers `amount` from `owner`'s account to `target_account`, on behalf of `owner`.
+    /// The caller must be `spender` and must have a sufficient allowance from `owner`, which
+    /// is decremented by `amount`.
+    TransferFrom {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    },
+    /// A transfer between confidential accounts, available only when the application was
+    /// created with a `confidential_policy`. The amount being moved is never revealed
+    /// on-chain; `proof` is verified and the accounts' commitments are homomorphically
+    /// updated instead. Rejected if `proof.range_proof` or `proof.equality_proof` fails to
+    /// verify.
+    ConfidentialTransfer {
+        owner: AccountOwner,
+        target_account: Account,
+        proof: ConfidentialTransferProof,
+    },
 }
 
 /// A message.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
-    /// Credit the given account.
+    /// Credit the given account. For cross-chain transfers, `amount` is already net of any
+    /// transfer fee.
     Credit { owner: AccountOwner, amount: Amount },
 
     /// Withdraw from the given account and starts a transfer to the target account.
@@ -223,6 +408,23 @@ pub enum Message {
         amount: Amount,
         target_account: Account,
     },
+
+    /// Adds a transfer fee to the accumulated fees on the `fee_collector`'s chain. Sent
+    /// alongside the `Credit` of a transfer subject to a `fee_policy` whenever the transfer is
+    /// debited on a chain other than the `fee_collector`'s; otherwise the fee is accumulated
+    /// locally and no `CreditFee` is sent.
+    CreditFee { amount: Amount },
+
+    /// Mints new tokens into the given account on a remote chain, as requested by a `Mint`
+    /// operation submitted on the chain holding the `mint_authority`.
+    Mint { owner: AccountOwner, amount: Amount },
+
+    /// Applies the receiver side of a confidential transfer on a remote chain: adds
+    /// `receiver_delta` to the target account's balance commitment.
+    ConfidentialCredit {
+        owner: AccountOwner,
+        receiver_delta: Commitment,
+    },
 }
 
 /// A cross-application call.
@@ -242,6 +444,15 @@ pub enum ApplicationCall {
         amount: Amount,
         target_account: Account,
     },
+    /// A transfer from `owner`'s account, on behalf of `owner`, made by a calling
+    /// application acting as `spender`. Draws down the allowance previously granted to
+    /// `spender` via the `Approve` operation, just like the `TransferFrom` operation.
+    TransferFrom {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    },
 }
 
 /// A cross-application call into a session.
@@ -351,8 +562,9 @@ pub struct InitialState {
 
 /// An account.
 #[derive(
-    Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize, InputObject,
+    Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize, SimpleObject, InputObject,
 )]
+#[graphql(input_name = "AccountInput")]
 pub struct Account {
     pub chain_id: ChainId,
     pub owner: AccountOwner,
@@ -360,7 +572,11 @@ pub struct Account {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Destination {
+    /// Credit the transferred amount to `Account` on its chain.
     Account(Account),
+    /// Open a new session holding the transferred amount. Not implemented yet: the contract
+    /// rejects transfers to `NewSession` rather than debiting an account for a session that is
+    /// never created.
     NewSession,
 }
 
@@ -395,6 +611,7 @@ impl FungibleTokenAbi {
     pub async fn create_with_accounts(
         validator: &TestValidator,
         bytecode_id: BytecodeId<Self>,
+        token_metadata: TokenMetadata,
         initial_amounts: impl IntoIterator<Item = Amount>,
     ) -> (
         ApplicationId<Self>,
@@ -418,7 +635,7 @@ impl FungibleTokenAbi {
         }
 
         let application_id = token_chain
-            .create_application(bytecode_id, (), initial_state.build(), vec![])
+            .create_application(bytecode_id, token_metadata, initial_state.build(), vec![])
             .await;
 
         for (chain, account, initial_amount) in &accounts {
@@ -483,3 +700,54 @@ impl FungibleTokenAbi {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_policy(fee_basis_points: u16, max_fee: Amount) -> FeePolicy {
+        FeePolicy {
+            fee_basis_points,
+            max_fee,
+            fee_collector: Account {
+                chain_id: ChainId::from_str(
+                    "0000000000000000000000000000000000000000000000000000000000000000",
+                )
+                .unwrap(),
+                owner: AccountOwner::from(Owner::from_str(
+                    "0000000000000000000000000000000000000000000000000000000000000000",
+                )
+                .unwrap()),
+            },
+        }
+    }
+
+    #[test]
+    fn compute_fee_takes_a_percentage_of_the_transfer() {
+        let policy = fee_policy(100, Amount::from_attos(u128::MAX));
+
+        assert_eq!(
+            policy.compute_fee(Amount::from_attos(10_000)),
+            Amount::from_attos(100)
+        );
+    }
+
+    #[test]
+    fn compute_fee_is_capped_at_max_fee() {
+        let policy = fee_policy(10_000, Amount::from_attos(1));
+
+        assert_eq!(
+            policy.compute_fee(Amount::from_attos(10_000)),
+            Amount::from_attos(1)
+        );
+    }
+
+    #[test]
+    fn compute_fee_does_not_inflate_by_going_through_the_whole_token_constructor() {
+        // `Amount::from(u128)` re-scales its argument by 10^18, so a regression that used it
+        // instead of `Amount::from_attos` here would massively overcharge this fee.
+        let policy = fee_policy(100, Amount::from_attos(u128::MAX));
+
+        assert!(policy.compute_fee(Amount::from_attos(10_000)) < Amount::from_attos(10_000));
+    }
+}
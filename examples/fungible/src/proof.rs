@@ -0,0 +1,293 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of [`ConfidentialTransferProof`]s against Pedersen commitments over the
+//! Ristretto255 group, as used by `Operation::ConfidentialTransfer`.
+//!
+//! A confidential account balance is a commitment `C = v*B + r*B_blinding`, where `v` is the
+//! balance and `r` is a blinding factor only the account owner knows. A transfer of value `v`
+//! supplies:
+//!
+//! - `sender_new_balance`, the sender's updated commitment `C_old - v*B - r_delta*B_blinding`,
+//! - `receiver_delta`, the commitment `v*B + r_delta*B_blinding` added to the receiver's balance,
+//! - a range proof that both of the above commit to values in `[0, 2^64)`, and
+//! - an equality proof that `C_old - sender_new_balance - receiver_delta` commits to zero, i.e.
+//!   that the transfer conserves value.
+//!
+//! None of this reveals `v` on-chain.
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof as BulletproofsRangeProof};
+use curve25519_dalek::{ristretto::CompressedRistretto, ristretto::RistrettoPoint, scalar::Scalar};
+use merlin::Transcript;
+use sha2::{Digest, Sha512};
+use thiserror::Error;
+
+use crate::{Commitment, ConfidentialTransferProof, RangeProof};
+
+/// Bit width used for every confidential balance and transfer amount's range proof.
+const RANGE_PROOF_BITS: usize = 64;
+
+/// The commitment to a zero balance with a zero blinding factor, used as the implicit balance of
+/// an account that has never been party to a confidential transfer.
+pub fn zero_commitment() -> Commitment {
+    Commitment(RistrettoPoint::default().compress().to_bytes())
+}
+
+/// Decompresses a [`Commitment`] into a Ristretto point.
+fn decompress(commitment: &Commitment) -> Result<RistrettoPoint, ProofError> {
+    CompressedRistretto(commitment.0)
+        .decompress()
+        .ok_or(ProofError::InvalidCommitment)
+}
+
+/// Homomorphically adds two commitments, i.e. commits to the sum of the values they each commit
+/// to.
+pub fn add(a: Commitment, b: Commitment) -> Result<Commitment, ProofError> {
+    let sum = decompress(&a)? + decompress(&b)?;
+    Ok(Commitment(sum.compress().to_bytes()))
+}
+
+/// Verifies a [`ConfidentialTransferProof`] against the sender's current balance commitment.
+/// Returns `Ok(())` only if the range proof and the equality (conservation-of-value) proof both
+/// check out.
+pub fn verify_confidential_transfer(
+    sender_old_balance: Commitment,
+    proof: &ConfidentialTransferProof,
+) -> Result<(), ProofError> {
+    verify_range_proof(
+        &[proof.sender_new_balance, proof.receiver_delta],
+        &proof.range_proof,
+    )?;
+
+    let conservation_point =
+        decompress(&sender_old_balance)? - decompress(&proof.sender_new_balance)? - decompress(&proof.receiver_delta)?;
+    verify_equality_proof(conservation_point, &proof.equality_proof)
+}
+
+/// Verifies that every commitment in `commitments` opens to a value in `[0, 2^64)`, using an
+/// aggregated Bulletproofs range proof.
+fn verify_range_proof(commitments: &[Commitment], range_proof: &RangeProof) -> Result<(), ProofError> {
+    let bp_gens = BulletproofGens::new(RANGE_PROOF_BITS, commitments.len());
+    let pc_gens = PedersenGens::default();
+    let compressed_commitments: Vec<CompressedRistretto> = commitments
+        .iter()
+        .map(|commitment| CompressedRistretto(commitment.0))
+        .collect();
+
+    let bp_proof = BulletproofsRangeProof::from_bytes(&range_proof.0)
+        .map_err(|_| ProofError::MalformedProof)?;
+    let mut transcript = Transcript::new(b"linera-fungible-confidential-transfer-range");
+    bp_proof
+        .verify_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &compressed_commitments,
+            RANGE_PROOF_BITS,
+        )
+        .map_err(|_| ProofError::RangeProofFailed)
+}
+
+/// Verifies a Schnorr proof of knowledge of the discrete log (base `B_blinding`) of
+/// `conservation_point`, which proves that it commits to the value zero without revealing the
+/// blinding factor. `equality_proof` is the 64-byte encoding `R || s` of the proof.
+fn verify_equality_proof(
+    conservation_point: RistrettoPoint,
+    equality_proof: &[u8],
+) -> Result<(), ProofError> {
+    if equality_proof.len() != 64 {
+        return Err(ProofError::MalformedProof);
+    }
+
+    let commitment_point = CompressedRistretto::from_slice(&equality_proof[..32])
+        .decompress()
+        .ok_or(ProofError::InvalidCommitment)?;
+    let response = Scalar::from_canonical_bytes(equality_proof[32..].try_into().unwrap())
+        .ok_or(ProofError::MalformedProof)?;
+
+    let challenge = fiat_shamir_challenge(&commitment_point, &conservation_point);
+    let pc_gens = PedersenGens::default();
+
+    if pc_gens.B_blinding * response == commitment_point + conservation_point * challenge {
+        Ok(())
+    } else {
+        Err(ProofError::EqualityProofFailed)
+    }
+}
+
+/// Derives the Fiat-Shamir challenge scalar for the equality proof from its commitment point and
+/// the conservation point it is proving a relation about.
+fn fiat_shamir_challenge(
+    commitment_point: &RistrettoPoint,
+    conservation_point: &RistrettoPoint,
+) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(b"linera-fungible-confidential-transfer-equality");
+    hasher.update(commitment_point.compress().as_bytes());
+    hasher.update(conservation_point.compress().as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+/// An error returned when a [`ConfidentialTransferProof`] fails to verify.
+#[derive(Clone, Copy, Debug, Error, Eq, PartialEq)]
+pub enum ProofError {
+    /// A commitment's bytes do not decompress to a valid Ristretto point.
+    #[error("Commitment does not decode to a valid Ristretto point")]
+    InvalidCommitment,
+
+    /// A proof's bytes are not in the expected format.
+    #[error("Proof is malformed")]
+    MalformedProof,
+
+    /// The range proof does not establish that the committed values lie in `[0, 2^64)`.
+    #[error("Range proof failed to verify")]
+    RangeProofFailed,
+
+    /// The equality proof does not establish that value was conserved by the transfer.
+    #[error("Equality proof failed to verify")]
+    EqualityProofFailed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commits to `value` with `blinding`, mirroring how a real client would derive the
+    /// `Commitment`s this module verifies.
+    fn commit(value: u64, blinding: Scalar) -> Commitment {
+        let pc_gens = PedersenGens::default();
+        Commitment(
+            pc_gens
+                .commit(Scalar::from(value), blinding)
+                .compress()
+                .to_bytes(),
+        )
+    }
+
+    /// Builds a valid Schnorr proof (as encoded by `verify_equality_proof`) that
+    /// `conservation_point` commits to zero with blinding factor `blinding`.
+    fn prove_equality(conservation_point: RistrettoPoint, blinding: Scalar) -> Vec<u8> {
+        let pc_gens = PedersenGens::default();
+        let nonce = Scalar::from(42u64);
+        let commitment_point = pc_gens.B_blinding * nonce;
+        let challenge = fiat_shamir_challenge(&commitment_point, &conservation_point);
+        let response = nonce + challenge * blinding;
+
+        let mut encoded = commitment_point.compress().to_bytes().to_vec();
+        encoded.extend_from_slice(response.as_bytes());
+        encoded
+    }
+
+    #[test]
+    fn zero_commitment_decompresses() {
+        decompress(&zero_commitment()).expect("the zero commitment must be a valid point");
+    }
+
+    #[test]
+    fn add_is_homomorphic_over_the_committed_values() {
+        let blinding_a = Scalar::from(7u64);
+        let blinding_b = Scalar::from(11u64);
+
+        let sum = add(commit(3, blinding_a), commit(4, blinding_b)).unwrap();
+
+        assert_eq!(sum, commit(7, blinding_a + blinding_b));
+    }
+
+    #[test]
+    fn verify_range_proof_accepts_values_within_range() {
+        let bp_gens = BulletproofGens::new(RANGE_PROOF_BITS, 2);
+        let pc_gens = PedersenGens::default();
+        let values = [5u64, 1_000u64];
+        let blindings = [Scalar::from(1u64), Scalar::from(2u64)];
+
+        let mut prover_transcript = Transcript::new(b"linera-fungible-confidential-transfer-range");
+        let (bp_proof, compressed_commitments) = BulletproofsRangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut prover_transcript,
+            &values,
+            &blindings,
+            RANGE_PROOF_BITS,
+        )
+        .unwrap();
+
+        let commitments: Vec<Commitment> = compressed_commitments
+            .into_iter()
+            .map(|point| Commitment(point.to_bytes()))
+            .collect();
+
+        verify_range_proof(&commitments, &RangeProof(bp_proof.to_bytes())).unwrap();
+    }
+
+    #[test]
+    fn verify_range_proof_rejects_malformed_bytes() {
+        let commitments = [zero_commitment()];
+
+        assert_eq!(
+            verify_range_proof(&commitments, &RangeProof(vec![0u8; 4])),
+            Err(ProofError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn verify_equality_proof_accepts_a_correctly_derived_proof() {
+        let blinding = Scalar::from(9u64);
+        let conservation_point = PedersenGens::default().B_blinding * blinding;
+
+        verify_equality_proof(conservation_point, &prove_equality(conservation_point, blinding))
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_equality_proof_rejects_a_mismatched_response() {
+        let blinding = Scalar::from(9u64);
+        let conservation_point = PedersenGens::default().B_blinding * blinding;
+        // Prove knowledge of a different blinding factor than the one `conservation_point`
+        // actually commits to.
+        let wrong_proof = prove_equality(conservation_point, blinding + Scalar::from(1u64));
+
+        assert_eq!(
+            verify_equality_proof(conservation_point, &wrong_proof),
+            Err(ProofError::EqualityProofFailed)
+        );
+    }
+
+    #[test]
+    fn verify_confidential_transfer_accepts_a_conserving_transfer() {
+        let old_blinding = Scalar::from(3u64);
+        let new_blinding = Scalar::from(4u64);
+        let delta_blinding = Scalar::from(5u64);
+
+        let sender_old_balance = commit(100, old_blinding);
+        let sender_new_balance = commit(70, new_blinding);
+        let receiver_delta = commit(30, delta_blinding);
+
+        let bp_gens = BulletproofGens::new(RANGE_PROOF_BITS, 2);
+        let pc_gens = PedersenGens::default();
+        let mut prover_transcript = Transcript::new(b"linera-fungible-confidential-transfer-range");
+        let (bp_proof, _) = BulletproofsRangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut prover_transcript,
+            &[70, 30],
+            &[new_blinding, delta_blinding],
+            RANGE_PROOF_BITS,
+        )
+        .unwrap();
+
+        let conservation_point = decompress(&sender_old_balance).unwrap()
+            - decompress(&sender_new_balance).unwrap()
+            - decompress(&receiver_delta).unwrap();
+        let conservation_blinding = old_blinding - new_blinding - delta_blinding;
+
+        let proof = ConfidentialTransferProof {
+            sender_new_balance,
+            receiver_delta,
+            range_proof: RangeProof(bp_proof.to_bytes()),
+            equality_proof: prove_equality(conservation_point, conservation_blinding),
+            auditor_ciphertext: None,
+        };
+
+        verify_confidential_transfer(sender_old_balance, &proof).unwrap();
+    }
+}
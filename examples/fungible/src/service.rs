@@ -0,0 +1,105 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::sync::Arc;
+
+use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
+use async_trait::async_trait;
+use fungible::{AccountOwner, FungibleTokenAbi, Operation, TokenMetadata};
+use linera_sdk::{base::Amount, graphql::GraphQLMutationRoot, Service, ServiceRuntime, ViewStateStorage};
+use state::FungibleTokenState;
+use thiserror::Error;
+
+linera_sdk::service!(FungibleTokenService);
+
+pub struct FungibleTokenService {
+    state: Arc<FungibleTokenState>,
+    runtime: Arc<ServiceRuntime<Self>>,
+}
+
+impl linera_sdk::base::WithServiceAbi for FungibleTokenService {
+    type Abi = FungibleTokenAbi;
+}
+
+#[async_trait]
+impl Service for FungibleTokenService {
+    type Error = Error;
+    type Storage = ViewStateStorage<Self>;
+
+    async fn handle_query(&self, request: Request) -> Result<Response, Self::Error> {
+        let schema = Schema::build(
+            QueryRoot {
+                state: self.state.clone(),
+            },
+            Operation::mutation_root(),
+            EmptySubscription,
+        )
+        .finish();
+        Ok(schema.execute(request).await)
+    }
+}
+
+struct QueryRoot {
+    state: Arc<FungibleTokenState>,
+}
+
+#[Object]
+impl QueryRoot {
+    /// Returns `account_owner`'s current balance, or zero if the account has never held tokens.
+    async fn accounts(&self, account_owner: AccountOwner) -> Amount {
+        self.state
+            .accounts
+            .get(&account_owner)
+            .await
+            .expect("Failed to read account balance")
+            .unwrap_or_default()
+    }
+
+    /// Returns the `TokenMetadata` (`name`, `ticker`, `decimals`, ...) this instance was created
+    /// with, echoing the application's `Parameters`.
+    async fn token_metadata(&self) -> TokenMetadata {
+        FungibleTokenService::parameters().expect("Application parameters must be set")
+    }
+
+    /// Returns the fees collected so far by this instance's `fee_policy` that have not yet been
+    /// withdrawn by the `fee_collector`, or zero if no `fee_policy` was configured.
+    async fn withdrawable_fees(&self) -> Amount {
+        *self.state.accumulated_fees.get()
+    }
+
+    /// Returns the supply recorded on this chain: the initial state, as adjusted by the `Mint`
+    /// and `Burn` operations and messages processed here. It is not a global counter.
+    async fn total_supply(&self) -> Amount {
+        *self.state.total_supply.get()
+    }
+
+    /// Returns the remaining amount `spender` may move out of `owner`'s account via
+    /// `TransferFrom`, as granted by `Approve`, or zero if none was granted.
+    async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Amount {
+        self.state
+            .allowances
+            .get(&(owner, spender))
+            .await
+            .expect("Failed to read allowance")
+            .unwrap_or_default()
+    }
+}
+
+impl FungibleTokenService {
+    fn parameters() -> Result<TokenMetadata, Error> {
+        linera_sdk::service::system_api::current_application_parameters()
+    }
+}
+
+/// An error that can occur while handling a GraphQL query against the fungible token
+/// application.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Query execution failed because the application state could not be read.
+    #[error(transparent)]
+    ViewError(#[from] linera_sdk::views::ViewError),
+}
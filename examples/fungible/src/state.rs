@@ -0,0 +1,36 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The application state for the `fungible` token example.
+
+use async_graphql::SimpleObject;
+use fungible::{AccountOwner, Commitment};
+use linera_sdk::{
+    base::{Amount, Timestamp},
+    views::{MapView, RegisterView, RootView, ViewStorageContext},
+};
+
+/// The application state, held in views so that only the parts that change are rewritten to
+/// storage.
+#[derive(RootView, SimpleObject)]
+#[view(context = "ViewStorageContext")]
+pub struct FungibleTokenState {
+    /// Plaintext balances, keyed by account owner. Kept for every instance, including one
+    /// created with a `confidential_policy`, whose commitment balances live separately in
+    /// `confidential_balances`.
+    pub accounts: MapView<AccountOwner, Amount>,
+    /// Pedersen commitments to balances, keyed by account owner, for an instance created with a
+    /// `confidential_policy`.
+    pub confidential_balances: MapView<AccountOwner, Commitment>,
+    /// Remaining amount that `spender` may move out of `owner`'s account via `TransferFrom`,
+    /// keyed by `(owner, spender)`, as granted by the `Approve` operation.
+    pub allowances: MapView<(AccountOwner, AccountOwner), Amount>,
+    /// The supply recorded on this chain: the initial state, adjusted by the `Mint` and `Burn`
+    /// processed here. Not a global counter.
+    pub total_supply: RegisterView<Amount>,
+    /// Fees collected so far by a `fee_policy` but not yet withdrawn by the `fee_collector`.
+    pub accumulated_fees: RegisterView<Amount>,
+    /// The start of the current rate-limiting window and the amount already withdrawn within
+    /// it, keyed by account owner, for an instance created with a `withdrawal_limit_policy`.
+    pub withdrawal_usage: MapView<AccountOwner, (Timestamp, Amount)>,
+}
@@ -0,0 +1,228 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use async_graphql::InputType;
+use fungible::{
+    Account, AccountOwner, FungibleTokenAbi, InitialStateBuilder, Operation, TokenMetadata,
+    WithdrawalLimitPolicy,
+};
+use futures::FutureExt;
+use linera_sdk::{base::Amount, test::TestValidator};
+
+/// A `TokenMetadata` with every optional policy unset, for tests that don't exercise one.
+fn bare_metadata() -> TokenMetadata {
+    TokenMetadata {
+        name: "Test Token".to_string(),
+        ticker: "TEST".to_string(),
+        decimals: 18,
+        fee_policy: None,
+        mint_authority: None,
+        withdrawal_limit_policy: None,
+        confidential_policy: None,
+    }
+}
+
+#[tokio::test]
+async fn mint_increases_balance_and_total_supply() {
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<FungibleTokenAbi>().await;
+    let mut chain = validator.new_chain().await;
+    let minter = AccountOwner::from(chain.public_key());
+
+    let mut metadata = bare_metadata();
+    metadata.mint_authority = Some(minter);
+
+    let application_id = chain
+        .create_application(
+            bytecode_id,
+            metadata,
+            InitialStateBuilder::default().build(),
+            vec![],
+        )
+        .await;
+    chain.register_application(application_id).await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Mint {
+                    to: Account {
+                        chain_id: chain.id(),
+                        owner: minter,
+                    },
+                    amount: Amount::from_attos(1_000),
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        FungibleTokenAbi::query_account(application_id, &chain, minter)
+            .await
+            .unwrap_or_default(),
+        Amount::from_attos(1_000)
+    );
+
+    let value = chain
+        .graphql_query(application_id, "query { totalSupply }".to_string())
+        .await;
+    let total_supply: Amount = value
+        .as_object()
+        .and_then(|object| object.get("totalSupply"))
+        .and_then(|value| value.as_str())
+        .expect("totalSupply query must return a string")
+        .parse()
+        .expect("totalSupply must be a valid Amount");
+
+    assert_eq!(total_supply, Amount::from_attos(1_000));
+}
+
+#[tokio::test]
+async fn burn_decreases_the_burned_account_balance() {
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<FungibleTokenAbi>().await;
+    let (application_id, accounts) = FungibleTokenAbi::create_with_accounts(
+        &validator,
+        bytecode_id,
+        bare_metadata(),
+        [Amount::from_attos(100)],
+    )
+    .await;
+    let (chain, owner, _initial_amount) = &accounts[0];
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Burn {
+                    from: *owner,
+                    amount: Amount::from_attos(40),
+                },
+            );
+        })
+        .await;
+
+    assert_eq!(
+        FungibleTokenAbi::query_account(application_id, chain, *owner)
+            .await
+            .unwrap_or_default(),
+        Amount::from_attos(60)
+    );
+}
+
+#[tokio::test]
+async fn transfer_from_decrements_the_allowance_it_spends() {
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<FungibleTokenAbi>().await;
+    let (application_id, accounts) = FungibleTokenAbi::create_with_accounts(
+        &validator,
+        bytecode_id,
+        bare_metadata(),
+        [Amount::from_attos(100)],
+    )
+    .await;
+    let (chain, owner, _initial_amount) = &accounts[0];
+    let spender = AccountOwner::from(validator.new_chain().await.public_key());
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Approve {
+                    spender,
+                    amount: Amount::from_attos(50),
+                },
+            );
+        })
+        .await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::TransferFrom {
+                    owner: *owner,
+                    spender,
+                    amount: Amount::from_attos(20),
+                    target_account: Account {
+                        chain_id: chain.id(),
+                        owner: spender,
+                    },
+                },
+            );
+        })
+        .await;
+
+    let query = format!(
+        "query {{ allowance(owner: {}, spender: {}) }}",
+        owner.to_value(),
+        spender.to_value()
+    );
+    let value = chain.graphql_query(application_id, query).await;
+    let remaining_allowance: Amount = value
+        .as_object()
+        .and_then(|object| object.get("allowance"))
+        .and_then(|value| value.as_str())
+        .expect("allowance query must return a string")
+        .parse()
+        .expect("allowance must be a valid Amount");
+
+    assert_eq!(remaining_allowance, Amount::from_attos(30));
+}
+
+#[tokio::test]
+async fn withdrawal_limit_rejects_a_transfer_that_would_exceed_it() {
+    let (validator, bytecode_id) = TestValidator::with_current_bytecode::<FungibleTokenAbi>().await;
+    let mut metadata = bare_metadata();
+    metadata.withdrawal_limit_policy = Some(WithdrawalLimitPolicy {
+        withdrawal_limit_per_epoch: Amount::from_attos(50),
+        epoch_duration_micros: u64::MAX,
+    });
+    // The account is created directly on the chain that transfers from it: funding it through
+    // `create_with_accounts` would claim all 100 at once, which the limit itself rejects.
+    let mut chain = validator.new_chain().await;
+    let owner = AccountOwner::from(chain.public_key());
+    let application_id = chain
+        .create_application(
+            bytecode_id,
+            metadata,
+            InitialStateBuilder::default()
+                .with_account(owner, Amount::from_attos(100))
+                .build(),
+            vec![],
+        )
+        .await;
+    let target = Account {
+        chain_id: chain.id(),
+        owner,
+    };
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Transfer {
+                    owner,
+                    amount: Amount::from_attos(30),
+                    target_account: target,
+                },
+            );
+        })
+        .await;
+
+    let second_withdrawal = std::panic::AssertUnwindSafe(chain.add_block(|block| {
+        block.with_operation(
+            application_id,
+            Operation::Transfer {
+                owner,
+                amount: Amount::from_attos(30),
+                target_account: target,
+            },
+        );
+    }));
+
+    assert!(
+        second_withdrawal.catch_unwind().await.is_err(),
+        "a transfer exceeding the remaining withdrawal limit for the epoch must be rejected"
+    );
+}